@@ -10,7 +10,7 @@ use std::{
     str::FromStr,
 };
 
-use serde::*;
+use serde::{de, *};
 
 pub trait Conversion {
     type Input;
@@ -67,6 +67,78 @@ where
     }
 }
 
+/// A map-like collection that can build itself from key/value pairs under
+/// an explicit duplicate-key policy, implemented for the two standard map
+/// types.
+pub trait MapInsert<K, V>: Default {
+    fn insert_last_wins(&mut self, key: K, val: V);
+    fn insert_first_wins(&mut self, key: K, val: V);
+}
+
+impl<K, V> MapInsert<K, V> for std::collections::HashMap<K, V>
+where
+    K: Eq + Hash,
+{
+    fn insert_last_wins(&mut self, key: K, val: V) {
+        self.insert(key, val);
+    }
+    fn insert_first_wins(&mut self, key: K, val: V) {
+        self.entry(key).or_insert(val);
+    }
+}
+
+impl<K, V> MapInsert<K, V> for std::collections::BTreeMap<K, V>
+where
+    K: Ord,
+{
+    fn insert_last_wins(&mut self, key: K, val: V) {
+        self.insert(key, val);
+    }
+    fn insert_first_wins(&mut self, key: K, val: V) {
+        self.entry(key).or_insert(val);
+    }
+}
+
+/// Collects an iterator of key/value pairs into a map, overwriting on each
+/// duplicate key so the last pair for a given key wins.
+pub struct CollectMapLastWins<K, V, I, C>(PhantomData<(K, V, I, C)>);
+
+impl<K, V, I, C> Conversion for CollectMapLastWins<K, V, I, C>
+where
+    C: MapInsert<K, V>,
+    I: IntoIterator<Item = (K, V)>,
+{
+    type Input = I;
+    type Output = C;
+    fn convert(val: I) -> Self::Output {
+        let mut map = C::default();
+        for (k, v) in val {
+            map.insert_last_wins(k, v);
+        }
+        map
+    }
+}
+
+/// Collects an iterator of key/value pairs into a map, inserting only when
+/// the key is absent so the first pair for a given key wins.
+pub struct CollectMapFirstWins<K, V, I, C>(PhantomData<(K, V, I, C)>);
+
+impl<K, V, I, C> Conversion for CollectMapFirstWins<K, V, I, C>
+where
+    C: MapInsert<K, V>,
+    I: IntoIterator<Item = (K, V)>,
+{
+    type Input = I;
+    type Output = C;
+    fn convert(val: I) -> Self::Output {
+        let mut map = C::default();
+        for (k, v) in val {
+            map.insert_first_wins(k, v);
+        }
+        map
+    }
+}
+
 pub struct TryConvert<T, U>(PhantomData<(T, U)>);
 
 impl<T, U> Conversion for TryConvert<T, U>
@@ -94,6 +166,30 @@ where
     }
 }
 
+/// Narrows `T` into `U`, clamping to `U`'s bounds instead of erroring
+/// (like [`TryConvert`]) or defaulting to zero (like
+/// [`TryConvertOrDefault`]) on overflow, so the migrated value stays as
+/// close to the original magnitude as the target type allows.
+pub struct Saturate<T, U>(PhantomData<(T, U)>);
+
+impl<T, U> Conversion for Saturate<T, U>
+where
+    T: Copy + PartialOrd + num_traits::Zero + TryInto<U>,
+    U: num_traits::Bounded,
+{
+    type Input = T;
+    type Output = U;
+    fn convert(val: T) -> Self::Output {
+        val.try_into().unwrap_or_else(|_| {
+            if val < T::zero() {
+                U::min_value()
+            } else {
+                U::max_value()
+            }
+        })
+    }
+}
+
 pub struct Parse<T>(PhantomData<T>);
 
 impl<T> Conversion for Parse<T>
@@ -159,6 +255,63 @@ where
     }
 }
 
+/// Threads a [`Conversion`] through an `Option`, converting `Some` and
+/// leaving `None` alone. Its `de` is the default [`Conversion::de`], so it
+/// still makes the untagged guess between `Option<F::Output>` and
+/// `Option<F::Input>`; a `null` is read as the new shape's `None`.
+pub struct ConvertOption<F>(PhantomData<F>);
+
+impl<F> Conversion for ConvertOption<F>
+where
+    F: Conversion,
+{
+    type Input = Option<F::Input>;
+    type Output = Option<F::Output>;
+    fn convert(val: Option<F::Input>) -> Self::Output {
+        val.map(F::convert)
+    }
+}
+
+/// Threads separate [`Conversion`]s through the keys and values of a
+/// collection of pairs, then rebuilds it via [`FromIterator`]. Needed
+/// whenever a map's key or value type changed shape, since the default
+/// [`Conversion::de`]'s untagged guess would have to apply to whole
+/// `(key, value)` tuples rather than each side independently.
+pub struct MapEntries<FK, FV, I, C>(PhantomData<(FK, FV, I, C)>);
+
+impl<FK, FV, I, C> Conversion for MapEntries<FK, FV, I, C>
+where
+    FK: Conversion,
+    FV: Conversion,
+    I: IntoIterator<Item = (FK::Input, FV::Input)>,
+    C: FromIterator<(FK::Output, FV::Output)>,
+{
+    type Input = I;
+    type Output = C;
+    fn convert(val: I) -> Self::Output {
+        val.into_iter()
+            .map(|(k, v)| (FK::convert(k), FV::convert(v)))
+            .collect()
+    }
+}
+
+/// Threads a [`Conversion`] through a `Vec`. Because `Deep<F>` is itself a
+/// [`Conversion`], nesting it (`Deep<Deep<F>>`, `Deep<Deep<Deep<F>>>`, ..)
+/// reaches through further levels of `Vec` nesting, the same way nested
+/// [`Compose`]s chain conversions.
+pub struct Deep<F>(PhantomData<F>);
+
+impl<F> Conversion for Deep<F>
+where
+    F: Conversion,
+{
+    type Input = Vec<F::Input>;
+    type Output = Vec<F::Output>;
+    fn convert(val: Vec<F::Input>) -> Self::Output {
+        val.into_iter().map(F::convert).collect()
+    }
+}
+
 pub struct ToString<T>(PhantomData<T>);
 
 impl<T> Conversion for ToString<T>
@@ -356,28 +509,1031 @@ where
     }
 }
 
-#[test]
-fn simple_test() {
-    #[derive(Serialize)]
-    struct OldFoo {
-        val: u64,
+/// Like [`Legacy`], but records *how* the value was obtained instead of
+/// discarding that information: whether it matched the new shape directly,
+/// or was lifted from a legacy representation via `C`, in which case the
+/// raw original is kept for logging, auditing, or one-time re-persisting
+/// migrated records.
+pub struct Annotated<C, T> {
+    pd: PhantomData<C>,
+    new: T,
+    original: Option<serde_json::Value>,
+}
+
+impl<C, T> Annotated<C, T> {
+    pub fn into_inner(annotated: Self) -> T {
+        annotated.new
     }
-    #[derive(Debug, PartialEq, Deserialize)]
-    struct NewFoo {
-        #[serde(
-            deserialize_with = "Compose::<TryConvertOrDefault::<u64, _>, Collect::<_, _>>::de",
-            alias = "val"
-        )]
-        vals: Vec<u32>,
+
+    pub fn was_migrated(&self) -> bool {
+        self.original.is_some()
     }
 
-    let old_foo_string = serde_json::to_string(&OldFoo { val: 5 }).unwrap();
-    let new_foo: NewFoo = serde_json::from_str(&old_foo_string).unwrap();
-    assert_eq!(new_foo, NewFoo { vals: vec![5] })
+    pub fn original(&self) -> Option<&serde_json::Value> {
+        self.original.as_ref()
+    }
 }
 
-#[test]
-fn legacy_test() {
-    let x = serde_json::from_str::<Legacy<ParseOrDefault<_>, u32>>(r#""5""#).unwrap();
-    assert_eq!(*x, 5);
+impl<C, T> fmt::Debug for Annotated<C, T>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.new.fmt(f)
+    }
+}
+
+impl<C, T> fmt::Display for Annotated<C, T>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.new.fmt(f)
+    }
+}
+
+impl<C, T> Clone for Annotated<C, T>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Annotated {
+            pd: PhantomData,
+            new: self.new.clone(),
+            original: self.original.clone(),
+        }
+    }
+}
+
+impl<C, T> PartialEq for Annotated<C, T>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.new == other.new
+    }
+}
+
+impl<C, T> Eq for Annotated<C, T> where T: Eq {}
+
+impl<C, T> PartialOrd for Annotated<C, T>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.new.partial_cmp(&other.new)
+    }
+}
+
+impl<C, T> Ord for Annotated<C, T>
+where
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.new.cmp(&other.new)
+    }
+}
+
+impl<C, T> Hash for Annotated<C, T>
+where
+    T: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.new.hash(state);
+    }
+}
+
+impl<C, T> Default for Annotated<C, T>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Annotated {
+            pd: PhantomData,
+            new: T::default(),
+            original: None,
+        }
+    }
+}
+
+impl<C, T> From<T> for Annotated<C, T> {
+    fn from(new: T) -> Self {
+        Annotated {
+            pd: PhantomData,
+            new,
+            original: None,
+        }
+    }
+}
+
+impl<C, T> AsRef<T> for Annotated<C, T> {
+    fn as_ref(&self) -> &T {
+        &self.new
+    }
+}
+
+impl<C, T> AsMut<T> for Annotated<C, T> {
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.new
+    }
+}
+
+impl<C, T> Borrow<T> for Annotated<C, T> {
+    fn borrow(&self) -> &T {
+        &self.new
+    }
+}
+
+impl<C, T> Deref for Annotated<C, T> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.new
+    }
+}
+
+impl<C, T> DerefMut for Annotated<C, T> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.new
+    }
+}
+
+impl<C, T> Serialize for Annotated<C, T>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.new.serialize(serializer)
+    }
+}
+
+impl<'de, C, T> Deserialize<'de> for Annotated<C, T>
+where
+    T: de::DeserializeOwned,
+    C: Conversion<Output = T>,
+    C::Input: de::DeserializeOwned,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let content = serde_json::Value::deserialize(deserializer)?;
+        if let Ok(new) = T::deserialize(&content) {
+            return Ok(Annotated {
+                new,
+                original: None,
+                pd: PhantomData,
+            });
+        }
+        let old = serde_json::from_value::<C::Input>(content.clone()).map_err(de::Error::custom)?;
+        Ok(Annotated {
+            new: C::convert(old),
+            original: Some(content),
+            pd: PhantomData,
+        })
+    }
+}
+
+/// A chain of [`Conversion`]s that lifts any historically valid shape of a
+/// type up to its `Latest` form, keyed by an explicit version number.
+///
+/// Implemented for tuples `(A, B, ..)` of [`Conversion`]s listed oldest
+/// first, where each conversion's `Input` is the previous conversion's
+/// `Output`. Version `0` names the oldest shape (the input of the first
+/// conversion); version `CURRENT` names `Latest` itself.
+///
+/// Only implemented for tuples up to 4 conversions (5 revisions) long; a
+/// longer lineage needs a new hand-written tuple impl following the same
+/// pattern.
+pub trait Chain {
+    type Latest;
+    /// The highest version this chain knows how to migrate from.
+    const CURRENT: u64;
+    /// Same-or-lower versions are migrated; anything newer was written by a
+    /// build this chain doesn't know about and must fail loudly rather than
+    /// be guessed at.
+    fn is_compatible(version: u64) -> bool {
+        version <= Self::CURRENT
+    }
+    fn from_version(version: u64, content: serde_json::Value) -> Result<Self::Latest, serde_json::Error>;
+}
+
+impl<A> Chain for (A,)
+where
+    A: Conversion,
+    A::Input: de::DeserializeOwned,
+    A::Output: de::DeserializeOwned,
+{
+    type Latest = A::Output;
+    const CURRENT: u64 = 1;
+    fn from_version(version: u64, content: serde_json::Value) -> Result<Self::Latest, serde_json::Error> {
+        match version {
+            0 => serde_json::from_value::<A::Input>(content).map(A::convert),
+            1 => serde_json::from_value::<A::Output>(content),
+            v => Err(de::Error::custom(format_args!("unsupported version {}", v))),
+        }
+    }
+}
+
+impl<A, B> Chain for (A, B)
+where
+    A: Conversion,
+    B: Conversion<Input = A::Output>,
+    A::Input: de::DeserializeOwned,
+    B::Input: de::DeserializeOwned,
+    B::Output: de::DeserializeOwned,
+{
+    type Latest = B::Output;
+    const CURRENT: u64 = 2;
+    fn from_version(version: u64, content: serde_json::Value) -> Result<Self::Latest, serde_json::Error> {
+        match version {
+            0 => serde_json::from_value::<A::Input>(content)
+                .map(A::convert)
+                .map(B::convert),
+            1 => serde_json::from_value::<B::Input>(content).map(B::convert),
+            2 => serde_json::from_value::<B::Output>(content),
+            v => Err(de::Error::custom(format_args!("unsupported version {}", v))),
+        }
+    }
+}
+
+impl<A, B, C> Chain for (A, B, C)
+where
+    A: Conversion,
+    B: Conversion<Input = A::Output>,
+    C: Conversion<Input = B::Output>,
+    A::Input: de::DeserializeOwned,
+    B::Input: de::DeserializeOwned,
+    C::Input: de::DeserializeOwned,
+    C::Output: de::DeserializeOwned,
+{
+    type Latest = C::Output;
+    const CURRENT: u64 = 3;
+    fn from_version(version: u64, content: serde_json::Value) -> Result<Self::Latest, serde_json::Error> {
+        match version {
+            0 => serde_json::from_value::<A::Input>(content)
+                .map(A::convert)
+                .map(B::convert)
+                .map(C::convert),
+            1 => serde_json::from_value::<B::Input>(content)
+                .map(B::convert)
+                .map(C::convert),
+            2 => serde_json::from_value::<C::Input>(content).map(C::convert),
+            3 => serde_json::from_value::<C::Output>(content),
+            v => Err(de::Error::custom(format_args!("unsupported version {}", v))),
+        }
+    }
+}
+
+impl<A, B, C, D> Chain for (A, B, C, D)
+where
+    A: Conversion,
+    B: Conversion<Input = A::Output>,
+    C: Conversion<Input = B::Output>,
+    D: Conversion<Input = C::Output>,
+    A::Input: de::DeserializeOwned,
+    B::Input: de::DeserializeOwned,
+    C::Input: de::DeserializeOwned,
+    D::Input: de::DeserializeOwned,
+    D::Output: de::DeserializeOwned,
+{
+    type Latest = D::Output;
+    const CURRENT: u64 = 4;
+    fn from_version(version: u64, content: serde_json::Value) -> Result<Self::Latest, serde_json::Error> {
+        match version {
+            0 => serde_json::from_value::<A::Input>(content)
+                .map(A::convert)
+                .map(B::convert)
+                .map(C::convert)
+                .map(D::convert),
+            1 => serde_json::from_value::<B::Input>(content)
+                .map(B::convert)
+                .map(C::convert)
+                .map(D::convert),
+            2 => serde_json::from_value::<C::Input>(content)
+                .map(C::convert)
+                .map(D::convert),
+            3 => serde_json::from_value::<D::Input>(content).map(D::convert),
+            4 => serde_json::from_value::<D::Output>(content),
+            v => Err(de::Error::custom(format_args!("unsupported version {}", v))),
+        }
+    }
+}
+
+/// Like [`Legacy`], but the serialized form carries an explicit `version`
+/// field instead of relying on an untagged old/new guess, so it can migrate
+/// a type across any number of past revisions via a [`Chain`].
+///
+/// A missing `version` field is treated as version `0`, the earliest known
+/// shape. A version newer than `C::CURRENT` is a hard error: it was written
+/// by a future, incompatible build and silently guessing at it would be
+/// worse than failing loudly.
+pub struct Versioned<T, C> {
+    pd: PhantomData<C>,
+    new: T,
+}
+
+impl<T, C> Versioned<T, C> {
+    pub fn into_inner(versioned: Self) -> T {
+        versioned.new
+    }
+}
+
+impl<T, C> fmt::Debug for Versioned<T, C>
+where
+    T: fmt::Debug,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.new.fmt(f)
+    }
+}
+
+impl<T, C> fmt::Display for Versioned<T, C>
+where
+    T: fmt::Display,
+{
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        self.new.fmt(f)
+    }
+}
+
+impl<T, C> Clone for Versioned<T, C>
+where
+    T: Clone,
+{
+    fn clone(&self) -> Self {
+        Versioned {
+            pd: PhantomData,
+            new: self.new.clone(),
+        }
+    }
+}
+
+impl<T, C> Copy for Versioned<T, C> where T: Copy {}
+
+impl<T, C> PartialEq for Versioned<T, C>
+where
+    T: PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.new == other.new
+    }
+}
+
+impl<T, C> Eq for Versioned<T, C> where T: Eq {}
+
+impl<T, C> PartialOrd for Versioned<T, C>
+where
+    T: PartialOrd,
+{
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        self.new.partial_cmp(&other.new)
+    }
+}
+
+impl<T, C> Ord for Versioned<T, C>
+where
+    T: Ord,
+{
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.new.cmp(&other.new)
+    }
+}
+
+impl<T, C> Hash for Versioned<T, C>
+where
+    T: Hash,
+{
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.new.hash(state);
+    }
+}
+
+impl<T, C> Default for Versioned<T, C>
+where
+    T: Default,
+{
+    fn default() -> Self {
+        Versioned {
+            pd: PhantomData,
+            new: T::default(),
+        }
+    }
+}
+
+impl<T, C> From<T> for Versioned<T, C> {
+    fn from(new: T) -> Self {
+        Versioned {
+            pd: PhantomData,
+            new,
+        }
+    }
+}
+
+impl<T, C> AsRef<T> for Versioned<T, C> {
+    fn as_ref(&self) -> &T {
+        &self.new
+    }
+}
+
+impl<T, C> AsMut<T> for Versioned<T, C> {
+    fn as_mut(&mut self) -> &mut T {
+        &mut self.new
+    }
+}
+
+impl<T, C> Borrow<T> for Versioned<T, C> {
+    fn borrow(&self) -> &T {
+        &self.new
+    }
+}
+
+impl<T, C> Deref for Versioned<T, C> {
+    type Target = T;
+    fn deref(&self) -> &Self::Target {
+        &self.new
+    }
+}
+
+impl<T, C> DerefMut for Versioned<T, C> {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.new
+    }
+}
+
+impl<T, C> Serialize for Versioned<T, C>
+where
+    T: Serialize,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.new.serialize(serializer)
+    }
+}
+
+impl<'de, T, C> Deserialize<'de> for Versioned<T, C>
+where
+    T: Deserialize<'de>,
+    C: Chain<Latest = T>,
+{
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let content = serde_json::Value::deserialize(deserializer)?;
+        let version = match content.get("version") {
+            None => 0,
+            Some(v) => v
+                .as_u64()
+                .ok_or_else(|| de::Error::custom(format_args!("version field is not a non-negative integer: {}", v)))?,
+        };
+        if !C::is_compatible(version) {
+            return Err(de::Error::custom(format_args!(
+                "data version {} is newer than the highest supported version {}",
+                version,
+                C::CURRENT
+            )));
+        }
+        C::from_version(version, content)
+            .map(|new| Versioned {
+                new,
+                pd: PhantomData,
+            })
+            .map_err(de::Error::custom)
+    }
+}
+
+pub trait VersionsChain {
+    type Output;
+    fn try_from_content(content: serde_json::Value) -> Result<Self::Output, serde_json::Error>;
+}
+
+impl<A, B> VersionsChain for (A, B)
+where
+    A: Conversion,
+    B: Conversion<Output = A::Output>,
+    A::Input: de::DeserializeOwned,
+    B::Input: de::DeserializeOwned,
+{
+    type Output = A::Output;
+    fn try_from_content(content: serde_json::Value) -> Result<Self::Output, serde_json::Error> {
+        if let Ok(a) = serde_json::from_value::<A::Input>(content.clone()) {
+            return Ok(A::convert(a));
+        }
+        serde_json::from_value::<B::Input>(content).map(B::convert)
+    }
+}
+
+impl<A, B, C> VersionsChain for (A, B, C)
+where
+    A: Conversion,
+    B: Conversion<Output = A::Output>,
+    C: Conversion<Output = A::Output>,
+    A::Input: de::DeserializeOwned,
+    B::Input: de::DeserializeOwned,
+    C::Input: de::DeserializeOwned,
+{
+    type Output = A::Output;
+    fn try_from_content(content: serde_json::Value) -> Result<Self::Output, serde_json::Error> {
+        if let Ok(a) = serde_json::from_value::<A::Input>(content.clone()) {
+            return Ok(A::convert(a));
+        }
+        if let Ok(b) = serde_json::from_value::<B::Input>(content.clone()) {
+            return Ok(B::convert(b));
+        }
+        serde_json::from_value::<C::Input>(content).map(C::convert)
+    }
+}
+
+impl<A, B, C, D> VersionsChain for (A, B, C, D)
+where
+    A: Conversion,
+    B: Conversion<Output = A::Output>,
+    C: Conversion<Output = A::Output>,
+    D: Conversion<Output = A::Output>,
+    A::Input: de::DeserializeOwned,
+    B::Input: de::DeserializeOwned,
+    C::Input: de::DeserializeOwned,
+    D::Input: de::DeserializeOwned,
+{
+    type Output = A::Output;
+    fn try_from_content(content: serde_json::Value) -> Result<Self::Output, serde_json::Error> {
+        if let Ok(a) = serde_json::from_value::<A::Input>(content.clone()) {
+            return Ok(A::convert(a));
+        }
+        if let Ok(b) = serde_json::from_value::<B::Input>(content.clone()) {
+            return Ok(B::convert(b));
+        }
+        if let Ok(c) = serde_json::from_value::<C::Input>(content.clone()) {
+            return Ok(C::convert(c));
+        }
+        serde_json::from_value::<D::Input>(content).map(D::convert)
+    }
+}
+
+/// A generalization of [`OldOrNew`]'s two-way guess to any number of
+/// previously-valid shapes, tried in the order given until one parses.
+///
+/// `T` is a tuple of [`Conversion`]s, newest-first, one per historical
+/// shape, each producing the same `Output`. Because the match is purely
+/// structural (like serde's `#[serde(untagged)]`), a permissive earlier
+/// variant can shadow a later, more specific one; list the most specific
+/// shapes first to keep the match deterministic.
+///
+/// Only implemented for tuples of 2 to 4 conversions; a longer lineage
+/// needs a new hand-written tuple impl following the same pattern.
+pub struct Versions<T>(PhantomData<T>);
+
+impl<T> Versions<T>
+where
+    T: VersionsChain,
+{
+    pub fn de<'de, D>(deserializer: D) -> Result<T::Output, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let content = serde_json::Value::deserialize(deserializer)?;
+        T::try_from_content(content).map_err(de::Error::custom)
+    }
+}
+
+#[test]
+fn versions_test() {
+    struct FromV2;
+    impl Conversion for FromV2 {
+        type Input = u32;
+        type Output = String;
+        fn convert(val: u32) -> String {
+            format!("v2:{}", val)
+        }
+    }
+    struct FromV1;
+    impl Conversion for FromV1 {
+        type Input = String;
+        type Output = String;
+        fn convert(val: String) -> String {
+            format!("v1:{}", val)
+        }
+    }
+    struct FromV0;
+    impl Conversion for FromV0 {
+        type Input = bool;
+        type Output = String;
+        fn convert(val: bool) -> String {
+            format!("v0:{}", val)
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "Versions::<(FromV2, FromV1, FromV0)>::de")] String);
+
+    let Wrapper(s) = serde_json::from_value(serde_json::json!(5)).unwrap();
+    assert_eq!(s, "v2:5");
+
+    let Wrapper(s) = serde_json::from_value(serde_json::json!("hi")).unwrap();
+    assert_eq!(s, "v1:hi");
+
+    let Wrapper(s) = serde_json::from_value(serde_json::json!(true)).unwrap();
+    assert_eq!(s, "v0:true");
+}
+
+#[test]
+fn versions_two_way_test() {
+    struct FromNew;
+    impl Conversion for FromNew {
+        type Input = u32;
+        type Output = String;
+        fn convert(val: u32) -> String {
+            format!("new:{}", val)
+        }
+    }
+    struct FromOld;
+    impl Conversion for FromOld {
+        type Input = bool;
+        type Output = String;
+        fn convert(val: bool) -> String {
+            format!("old:{}", val)
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Wrapper(#[serde(deserialize_with = "Versions::<(FromNew, FromOld)>::de")] String);
+
+    let Wrapper(s) = serde_json::from_value(serde_json::json!(5)).unwrap();
+    assert_eq!(s, "new:5");
+
+    let Wrapper(s) = serde_json::from_value(serde_json::json!(true)).unwrap();
+    assert_eq!(s, "old:true");
+}
+
+#[test]
+fn versions_four_way_test() {
+    struct FromV3;
+    impl Conversion for FromV3 {
+        type Input = u32;
+        type Output = String;
+        fn convert(val: u32) -> String {
+            format!("v3:{}", val)
+        }
+    }
+    struct FromV2;
+    impl Conversion for FromV2 {
+        type Input = String;
+        type Output = String;
+        fn convert(val: String) -> String {
+            format!("v2:{}", val)
+        }
+    }
+    struct FromV1;
+    impl Conversion for FromV1 {
+        type Input = bool;
+        type Output = String;
+        fn convert(val: bool) -> String {
+            format!("v1:{}", val)
+        }
+    }
+    struct FromV0;
+    impl Conversion for FromV0 {
+        type Input = Vec<u8>;
+        type Output = String;
+        fn convert(val: Vec<u8>) -> String {
+            format!("v0:{:?}", val)
+        }
+    }
+
+    #[derive(Deserialize)]
+    struct Wrapper(
+        #[serde(deserialize_with = "Versions::<(FromV3, FromV2, FromV1, FromV0)>::de")] String,
+    );
+
+    let Wrapper(s) = serde_json::from_value(serde_json::json!(5)).unwrap();
+    assert_eq!(s, "v3:5");
+
+    let Wrapper(s) = serde_json::from_value(serde_json::json!("hi")).unwrap();
+    assert_eq!(s, "v2:hi");
+
+    let Wrapper(s) = serde_json::from_value(serde_json::json!(true)).unwrap();
+    assert_eq!(s, "v1:true");
+
+    let Wrapper(s) = serde_json::from_value(serde_json::json!([1, 2])).unwrap();
+    assert_eq!(s, "v0:[1, 2]");
+}
+
+#[test]
+fn versioned_test() {
+    #[derive(Deserialize)]
+    struct FooV0 {
+        count: u32,
+    }
+    struct LiftV0;
+    impl Conversion for LiftV0 {
+        type Input = FooV0;
+        type Output = FooV1;
+        fn convert(val: FooV0) -> FooV1 {
+            FooV1 {
+                count: val.count as u64,
+                label: String::new(),
+            }
+        }
+    }
+    #[derive(Deserialize)]
+    struct FooV1 {
+        count: u64,
+        label: String,
+    }
+    struct LiftV1;
+    impl Conversion for LiftV1 {
+        type Input = FooV1;
+        type Output = Foo;
+        fn convert(val: FooV1) -> Foo {
+            Foo {
+                count: val.count,
+                label: val.label,
+            }
+        }
+    }
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Foo {
+        count: u64,
+        label: String,
+    }
+
+    type FooChain = (LiftV0, LiftV1);
+
+    let v0 = serde_json::json!({ "count": 3 });
+    let got: Versioned<Foo, FooChain> = serde_json::from_value(v0).unwrap();
+    assert_eq!(
+        *got,
+        Foo {
+            count: 3,
+            label: String::new()
+        }
+    );
+
+    let v2 = serde_json::json!({ "version": 2, "count": 7, "label": "hi" });
+    let got: Versioned<Foo, FooChain> = serde_json::from_value(v2).unwrap();
+    assert_eq!(
+        *got,
+        Foo {
+            count: 7,
+            label: "hi".into()
+        }
+    );
+
+    let future = serde_json::json!({ "version": 99 });
+    let err = serde_json::from_value::<Versioned<Foo, FooChain>>(future).unwrap_err();
+    assert!(err.to_string().contains("newer"));
+}
+
+#[test]
+fn versioned_three_link_chain_test() {
+    #[derive(Deserialize)]
+    struct BarV0 {
+        n: u32,
+    }
+    struct LiftV0;
+    impl Conversion for LiftV0 {
+        type Input = BarV0;
+        type Output = BarV1;
+        fn convert(val: BarV0) -> BarV1 {
+            BarV1 { n: val.n as u64 }
+        }
+    }
+    #[derive(Deserialize)]
+    struct BarV1 {
+        n: u64,
+    }
+    struct LiftV1;
+    impl Conversion for LiftV1 {
+        type Input = BarV1;
+        type Output = BarV2;
+        fn convert(val: BarV1) -> BarV2 {
+            BarV2 {
+                n: val.n,
+                tag: String::new(),
+            }
+        }
+    }
+    #[derive(Deserialize)]
+    struct BarV2 {
+        n: u64,
+        tag: String,
+    }
+    struct LiftV2;
+    impl Conversion for LiftV2 {
+        type Input = BarV2;
+        type Output = Bar;
+        fn convert(val: BarV2) -> Bar {
+            Bar {
+                n: val.n,
+                tag: val.tag,
+            }
+        }
+    }
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Bar {
+        n: u64,
+        tag: String,
+    }
+
+    type BarChain = (LiftV0, LiftV1, LiftV2);
+
+    let v0 = serde_json::json!({ "n": 1 });
+    let got: Versioned<Bar, BarChain> = serde_json::from_value(v0).unwrap();
+    assert_eq!(
+        *got,
+        Bar {
+            n: 1,
+            tag: String::new()
+        }
+    );
+
+    let v1 = serde_json::json!({ "version": 1, "n": 2 });
+    let got: Versioned<Bar, BarChain> = serde_json::from_value(v1).unwrap();
+    assert_eq!(
+        *got,
+        Bar {
+            n: 2,
+            tag: String::new()
+        }
+    );
+
+    let v3 = serde_json::json!({ "version": 3, "n": 3, "tag": "latest" });
+    let got: Versioned<Bar, BarChain> = serde_json::from_value(v3).unwrap();
+    assert_eq!(
+        *got,
+        Bar {
+            n: 3,
+            tag: "latest".into()
+        }
+    );
+}
+
+#[test]
+fn versioned_rejects_non_integer_version_test() {
+    type FooChain = (LiftV0Marker, LiftV1Marker);
+    struct LiftV0Marker;
+    impl Conversion for LiftV0Marker {
+        type Input = u32;
+        type Output = u64;
+        fn convert(val: u32) -> u64 {
+            val as u64
+        }
+    }
+    struct LiftV1Marker;
+    impl Conversion for LiftV1Marker {
+        type Input = u64;
+        type Output = u64;
+        fn convert(val: u64) -> u64 {
+            val
+        }
+    }
+
+    let bad = serde_json::json!({ "version": "2", "n": 1 });
+    let err = serde_json::from_value::<Versioned<u64, FooChain>>(bad).unwrap_err();
+    assert!(err.to_string().contains("not a non-negative integer"));
+
+    let bad = serde_json::json!({ "version": -1, "n": 1 });
+    let err = serde_json::from_value::<Versioned<u64, FooChain>>(bad).unwrap_err();
+    assert!(err.to_string().contains("not a non-negative integer"));
+}
+
+#[test]
+fn simple_test() {
+    #[derive(Serialize)]
+    struct OldFoo {
+        val: u64,
+    }
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct NewFoo {
+        #[serde(
+            deserialize_with = "Compose::<TryConvertOrDefault::<u64, _>, Collect::<_, _>>::de",
+            alias = "val"
+        )]
+        vals: Vec<u32>,
+    }
+
+    let old_foo_string = serde_json::to_string(&OldFoo { val: 5 }).unwrap();
+    let new_foo: NewFoo = serde_json::from_str(&old_foo_string).unwrap();
+    assert_eq!(new_foo, NewFoo { vals: vec![5] })
+}
+
+#[test]
+fn legacy_test() {
+    let x = serde_json::from_str::<Legacy<ParseOrDefault<_>, u32>>(r#""5""#).unwrap();
+    assert_eq!(*x, 5);
+}
+
+#[test]
+fn annotated_test() {
+    let fresh = serde_json::from_str::<Annotated<ParseOrDefault<u32>, u32>>("5").unwrap();
+    assert_eq!(*fresh, 5);
+    assert!(!fresh.was_migrated());
+    assert!(fresh.original().is_none());
+
+    let migrated = serde_json::from_str::<Annotated<ParseOrDefault<u32>, u32>>(r#""5""#).unwrap();
+    assert_eq!(*migrated, 5);
+    assert!(migrated.was_migrated());
+    assert_eq!(migrated.original(), Some(&serde_json::json!("5")));
+}
+
+#[test]
+fn convert_option_test() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Foo {
+        #[serde(deserialize_with = "ConvertOption::<ParseOrDefault<u32>>::de")]
+        val: Option<u32>,
+    }
+
+    let foo: Foo = serde_json::from_str(r#"{"val": "5"}"#).unwrap();
+    assert_eq!(foo, Foo { val: Some(5) });
+
+    let foo: Foo = serde_json::from_str(r#"{"val": null}"#).unwrap();
+    assert_eq!(foo, Foo { val: None });
+}
+
+#[test]
+fn map_entries_test() {
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Foo {
+        #[serde(deserialize_with = "MapEntries::<ParseOrDefault<u32>, ParseOrDefault<u32>, Vec<(String, String)>, HashMap<u32, u32>>::de")]
+        entries: HashMap<u32, u32>,
+    }
+
+    let foo: Foo = serde_json::from_str(r#"{"entries": [["1", "2"], ["3", "4"]]}"#).unwrap();
+    let mut expected = HashMap::new();
+    expected.insert(1, 2);
+    expected.insert(3, 4);
+    assert_eq!(foo, Foo { entries: expected });
+}
+
+#[test]
+fn deep_test() {
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Foo {
+        #[serde(deserialize_with = "Deep::<Deep<ParseOrDefault<u32>>>::de")]
+        rows: Vec<Vec<u32>>,
+    }
+
+    let foo: Foo = serde_json::from_str(r#"{"rows": [["1", "2"], ["3"]]}"#).unwrap();
+    assert_eq!(
+        foo,
+        Foo {
+            rows: vec![vec![1, 2], vec![3]]
+        }
+    );
+}
+
+#[test]
+fn collect_map_last_wins_test() {
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Foo {
+        #[serde(
+            deserialize_with = "CollectMapLastWins::<u32, u32, Vec<(u32, u32)>, HashMap<u32, u32>>::de",
+            alias = "pairs"
+        )]
+        map: HashMap<u32, u32>,
+    }
+
+    let foo: Foo = serde_json::from_str(r#"{"pairs": [[1, 10], [1, 20], [2, 30]]}"#).unwrap();
+    let mut expected = HashMap::new();
+    expected.insert(1, 20);
+    expected.insert(2, 30);
+    assert_eq!(foo, Foo { map: expected });
+}
+
+#[test]
+fn collect_map_first_wins_test() {
+    use std::collections::HashMap;
+
+    #[derive(Debug, PartialEq, Deserialize)]
+    struct Foo {
+        #[serde(
+            deserialize_with = "CollectMapFirstWins::<u32, u32, Vec<(u32, u32)>, HashMap<u32, u32>>::de",
+            alias = "pairs"
+        )]
+        map: HashMap<u32, u32>,
+    }
+
+    let foo: Foo = serde_json::from_str(r#"{"pairs": [[1, 10], [1, 20], [2, 30]]}"#).unwrap();
+    let mut expected = HashMap::new();
+    expected.insert(1, 10);
+    expected.insert(2, 30);
+    assert_eq!(foo, Foo { map: expected });
+}
+
+#[test]
+fn saturate_test() {
+    assert_eq!(Saturate::<u64, u32>::convert(5), 5);
+    assert_eq!(Saturate::<u64, u32>::convert(u64::MAX), u32::MAX);
+    assert_eq!(Saturate::<i64, i32>::convert(i64::MIN), i32::MIN);
+    assert_eq!(Saturate::<i32, u32>::convert(-1), 0);
 }